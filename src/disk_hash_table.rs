@@ -0,0 +1,584 @@
+//! A disk-backed / memory-mapped implementation of [`HashTable`] for datasets
+//! larger than RAM.
+//!
+//! Instead of holding keys and values inline in the bucket array, entries live in
+//! a memory-mapped value region whose layout is borrowed from `parity-db`'s
+//! value tables:
+//!
+//! ```text
+//! header:  [LAST_REMOVED: 8][FILLED: 8]
+//! record:  [SIZE: 2][KEY_LEN: 2][KEY: KEY_LEN][VALUE: 4]
+//! ```
+//!
+//! `SIZE` is the record's *physical* extent (the bytes reserved for the slot), not
+//! the logical body length: when a freed slot is reused by a smaller record it
+//! keeps the larger extent, so a file-order walk always advances by the real slot
+//! size and never misparses a shrunk slot's dead tail as a record.
+//!
+//! `FILLED` is the high-water mark (first unused byte); `LAST_REMOVED` is the head
+//! of a free-list of reclaimed records. A deleted record is pushed onto the
+//! free-list by writing the previous `LAST_REMOVED` into its leading bytes, so
+//! [`insert`](DiskStrHashTable::insert) can reuse the space. The in-memory bucket
+//! array only stores the file offset of each record (plus a cached value, so the
+//! trait's `&V` lookups stay zero-copy), which keeps the resident footprint tiny
+//! regardless of corpus size.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use compact_str::CompactString;
+use fxhash::hash as fxhash;
+use memmap2::MmapMut;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+
+use crate::fixed_hash_table::HashTable;
+
+type SKeyType = CompactString;
+type SValueType = u32;
+
+// Header field offsets and the first byte available to records.
+const LAST_REMOVED: usize = 0;
+const FILLED: usize = 8;
+const HEADER_LEN: u64 = 16;
+
+// Free-list terminator. Offset 0 is inside the header, so it can never be a real
+// record offset and doubles as the nil sentinel.
+const NIL: u64 = 0;
+
+// A freed record stores `[PREV_HEAD: 8][PHYSICAL: 2]`, so every record must be at
+// least this many bytes for the link and size to survive deletion.
+const MIN_RECORD: usize = 10;
+
+/// Backing store for the value region: either a plain growable buffer (for the
+/// transient [`HashTable::new`] constructor) or a memory-mapped file (via
+/// [`DiskStrHashTable::open`]).
+enum Storage {
+    Mem(Vec<u8>),
+    Mmap {
+        file: std::fs::File,
+        map: MmapMut,
+    },
+}
+
+impl Storage {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Storage::Mem(buf) => buf,
+            Storage::Mmap { map, .. } => map,
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        match self {
+            Storage::Mem(buf) => buf,
+            Storage::Mmap { map, .. } => map,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Ensures the backing store is at least `needed` bytes, doubling past that so
+    /// growth is amortized. Remaps the file when it is mmap-backed.
+    fn ensure(&mut self, needed: usize) {
+        if self.len() >= needed {
+            return;
+        }
+        let new_len = needed.next_power_of_two().max(self.len() * 2);
+        match self {
+            Storage::Mem(buf) => buf.resize(new_len, 0),
+            Storage::Mmap { file, map } => {
+                file.set_len(new_len as u64).expect("cannot grow value file");
+                // SAFETY: the file was just resized and is owned by this handle.
+                *map = unsafe { MmapMut::map_mut(&*file).expect("cannot remap value file") };
+            }
+        }
+    }
+}
+
+/// The value region: a `parity-db`-style table of variable-length key/value
+/// records with a free-list of reclaimed slots.
+struct ValueTable {
+    storage: Storage,
+}
+
+impl ValueTable {
+    fn from_storage(mut storage: Storage) -> Self {
+        if storage.len() < HEADER_LEN as usize {
+            storage.ensure(HEADER_LEN as usize);
+        }
+        let mut table = Self { storage };
+        if table.read_u64(FILLED) < HEADER_LEN {
+            // Fresh region: no records yet, free-list empty.
+            table.write_u64(FILLED, HEADER_LEN);
+            table.write_u64(LAST_REMOVED, NIL);
+        }
+        table
+    }
+
+    fn read_u64(&self, at: usize) -> u64 {
+        u64::from_le_bytes(self.storage.as_slice()[at..at + 8].try_into().unwrap())
+    }
+
+    fn write_u64(&mut self, at: usize, v: u64) {
+        self.storage.as_mut_slice()[at..at + 8].copy_from_slice(&v.to_le_bytes());
+    }
+
+    fn read_u16(&self, at: usize) -> u16 {
+        u16::from_le_bytes(self.storage.as_slice()[at..at + 2].try_into().unwrap())
+    }
+
+    fn write_u16(&mut self, at: usize, v: u16) {
+        self.storage.as_mut_slice()[at..at + 2].copy_from_slice(&v.to_le_bytes());
+    }
+
+    fn read_u32(&self, at: usize) -> u32 {
+        u32::from_le_bytes(self.storage.as_slice()[at..at + 4].try_into().unwrap())
+    }
+
+    /// Physical footprint of a record with the given body length.
+    fn physical(body: usize) -> usize {
+        body.max(MIN_RECORD)
+    }
+
+    /// Key bytes of the live record at `offset`.
+    fn key_at(&self, offset: u64) -> &str {
+        let off = offset as usize;
+        let key_len = self.read_u16(off + 2) as usize;
+        let bytes = &self.storage.as_slice()[off + 4..off + 4 + key_len];
+        core::str::from_utf8(bytes).expect("record keys are always valid UTF-8")
+    }
+
+    /// Value of the live record at `offset`.
+    fn value_at(&self, offset: u64) -> SValueType {
+        let off = offset as usize;
+        let key_len = self.read_u16(off + 2) as usize;
+        self.read_u32(off + 4 + key_len)
+    }
+
+    fn set_value_at(&mut self, offset: u64, value: SValueType) {
+        let off = offset as usize;
+        let key_len = self.read_u16(off + 2) as usize;
+        self.storage.as_mut_slice()[off + 4 + key_len..off + 8 + key_len]
+            .copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Allocates a record for `key`/`value`, reusing the free-list head when it is
+    /// large enough and otherwise bumping the high-water mark. Returns the offset.
+    fn store(&mut self, key: &str, value: SValueType) -> u64 {
+        let body = 2 + 2 + key.len() + 4;
+        let min_physical = Self::physical(body);
+
+        let head = self.read_u64(LAST_REMOVED);
+        let head_physical = if head != NIL {
+            self.read_u16(head as usize + 8) as usize
+        } else {
+            0
+        };
+        let (offset, physical) = if head != NIL && head_physical >= min_physical {
+            // Reuse the freed slot: pop it off the free-list. Keep the slot's own
+            // physical extent so the file-order walk still advances correctly even
+            // when the new record is smaller than the one it replaces.
+            let next = self.read_u64(head as usize);
+            self.write_u64(LAST_REMOVED, next);
+            (head, head_physical)
+        } else {
+            // Append at the high-water mark, growing the region if needed.
+            let offset = self.read_u64(FILLED);
+            self.storage.ensure(offset as usize + min_physical);
+            self.write_u64(FILLED, offset + min_physical as u64);
+            (offset, min_physical)
+        };
+
+        let off = offset as usize;
+        self.write_u16(off, physical as u16);
+        self.write_u16(off + 2, key.len() as u16);
+        self.storage.as_mut_slice()[off + 4..off + 4 + key.len()].copy_from_slice(key.as_bytes());
+        self.storage.as_mut_slice()[off + 4 + key.len()..off + 8 + key.len()]
+            .copy_from_slice(&value.to_le_bytes());
+        offset
+    }
+
+    /// Links the record at `offset` into the free-list, recording its physical
+    /// size so a future `store` can tell whether it fits.
+    fn free(&mut self, offset: u64) {
+        let off = offset as usize;
+        // `SIZE` already holds the physical extent of the slot.
+        let physical = self.read_u16(off) as usize;
+        let prev = self.read_u64(LAST_REMOVED);
+        self.write_u64(off, prev);
+        self.write_u16(off + 8, physical as u16);
+        self.write_u64(LAST_REMOVED, offset);
+    }
+
+    /// Number of live (non-freed) records currently in the region. Used to size the
+    /// reconstructed bucket array on open, independently of the caller's hint.
+    fn count_live(&self) -> usize {
+        let mut freed = alloc::collections::BTreeSet::new();
+        let mut head = self.read_u64(LAST_REMOVED);
+        while head != NIL {
+            freed.insert(head);
+            head = self.read_u64(head as usize);
+        }
+
+        let filled = self.read_u64(FILLED);
+        let mut offset = HEADER_LEN;
+        let mut count = 0;
+        while offset < filled {
+            if freed.contains(&offset) {
+                offset += self.read_u16(offset as usize + 8) as u64;
+            } else {
+                count += 1;
+                offset += self.read_u16(offset as usize) as u64;
+            }
+        }
+        count
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Bucket {
+    Empty,
+    /// Offset of the on-disk record, a cached copy of the value and the index into
+    /// the insertion-order vector.
+    Occupied(u64, SValueType, usize),
+    Deleted,
+}
+
+/// A [`HashTable`] whose keys and values are persisted in a memory-mapped value
+/// region, keeping only offsets resident in memory.
+pub struct DiskStrHashTable {
+    buckets: Vec<Bucket>,
+    by_insertion: Vec<Option<usize>>,
+    values: ValueTable,
+    capacity: usize,
+    size: usize,
+    first: Option<(usize, usize)>,
+    last: Option<(usize, usize)>,
+    // Owned copies of the first/last keys. The records themselves live in the
+    // value region, but `get_first`/`get_last` must hand back a `&SKeyType`, so we
+    // materialize those two endpoints whenever they change.
+    first_key: Option<SKeyType>,
+    last_key: Option<SKeyType>,
+}
+
+impl DiskStrHashTable {
+    /// Opens (or creates) a persistent table backed by the file at `path`,
+    /// reconstructing the in-memory bucket array from the records already present.
+    pub fn open(path: impl AsRef<Path>, min_capacity: usize) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        if file.metadata()?.len() < HEADER_LEN {
+            file.set_len(HEADER_LEN)?;
+        }
+        // SAFETY: we hold the only handle to this freshly-opened file.
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let values = ValueTable::from_storage(Storage::Mmap { file, map });
+        Ok(Self::from_values(values, min_capacity))
+    }
+
+    /// Flushes any outstanding writes to disk. A no-op for the transient
+    /// (in-memory) backend.
+    pub fn flush(&self) -> io::Result<()> {
+        match &self.values.storage {
+            Storage::Mmap { map, .. } => map.flush(),
+            Storage::Mem(_) => Ok(()),
+        }
+    }
+
+    fn from_values(values: ValueTable, min_capacity: usize) -> Self {
+        // Size the table from the records actually present so reopening a file that
+        // holds more than `~0.75 * min_capacity` entries does not overflow the
+        // bucket array and silently drop records. Keep it under the 0.75 load cap.
+        let live = values.count_live();
+        let needed = min_capacity.max(((live as f64) / 0.75) as usize + 1);
+        let capacity = needed.next_power_of_two();
+        let mut table = Self {
+            buckets: alloc::vec![Bucket::Empty; capacity],
+            by_insertion: Vec::new(),
+            values,
+            capacity,
+            size: 0,
+            first: None,
+            last: None,
+            first_key: None,
+            last_key: None,
+        };
+        table.rebuild_index();
+        table.refresh_endpoint_keys();
+        table
+    }
+
+    /// Recomputes the cached `first_key`/`last_key` from the current endpoints.
+    fn refresh_endpoint_keys(&mut self) {
+        self.first_key = self
+            .first
+            .map(|(_, bucket_i)| CompactString::from(self.values.key_at(self.offset_of(bucket_i))));
+        self.last_key = self
+            .last
+            .map(|(_, bucket_i)| CompactString::from(self.values.key_at(self.offset_of(bucket_i))));
+    }
+
+    fn offset_of(&self, bucket_i: usize) -> u64 {
+        match self.buckets[bucket_i] {
+            Bucket::Occupied(offset, ..) => offset,
+            _ => panic!("Index to deleted entry"),
+        }
+    }
+
+    /// Walks the value region in file order and rehashes every live record back
+    /// into the bucket array.
+    ///
+    /// Insertion order is *not* persisted (`by_insertion` is in-memory only), and
+    /// `remove` links freed slots into a free-list that `store` reuses out of
+    /// order, so the reconstructed order is the records' physical storage order,
+    /// not the original insertion order. After a reopen that followed any removals,
+    /// `get_first`/`get_last` therefore reflect storage order, not insertion order.
+    fn rebuild_index(&mut self) {
+        // Collect the free-list so freed records are skipped during the walk.
+        let mut freed = alloc::collections::BTreeSet::new();
+        let mut head = self.values.read_u64(LAST_REMOVED);
+        while head != NIL {
+            freed.insert(head);
+            head = self.values.read_u64(head as usize);
+        }
+
+        let filled = self.values.read_u64(FILLED);
+        let mut offset = HEADER_LEN;
+        while offset < filled {
+            if freed.contains(&offset) {
+                // A freed record keeps its physical extent at `off + 8`.
+                let physical = self.values.read_u16(offset as usize + 8) as usize;
+                offset += physical as u64;
+                continue;
+            }
+            // `SIZE` is the physical extent of a live record.
+            let physical = self.values.read_u16(offset as usize) as usize;
+            let key = CompactString::from(self.values.key_at(offset));
+            let value = self.values.value_at(offset);
+            self.index_existing(key, value, offset);
+            offset += physical as u64;
+        }
+    }
+
+    /// Doubles the bucket array and re-indexes every live entry into it. The
+    /// records themselves do not move — only the resident offset map is rebuilt —
+    /// so rehashing is cheap and preserves the current in-memory order.
+    fn grow(&mut self) {
+        let new_capacity = self.capacity * 2;
+        let old_buckets =
+            core::mem::replace(&mut self.buckets, alloc::vec![Bucket::Empty; new_capacity]);
+        let old_order = core::mem::take(&mut self.by_insertion);
+
+        self.capacity = new_capacity;
+        self.size = 0;
+        self.first = None;
+        self.last = None;
+
+        for slot in old_order.into_iter().flatten() {
+            if let Bucket::Occupied(offset, value, _) = old_buckets[slot] {
+                let key = CompactString::from(self.values.key_at(offset));
+                self.index_existing(key, value, offset);
+            }
+        }
+        self.refresh_endpoint_keys();
+    }
+
+    /// Inserts an already-persisted record into the bucket array (used on open).
+    fn index_existing(&mut self, key: SKeyType, value: SValueType, offset: u64) {
+        let h = fxhash(&key);
+        let max_attempts = (0.75 * (self.capacity as f64)) as usize;
+        for i in 0..max_attempts {
+            let bucket_i = (h + i) & (self.capacity - 1);
+            if let Bucket::Empty = self.buckets[bucket_i] {
+                self.by_insertion.push(Some(bucket_i));
+                let insertion_i = self.by_insertion.len() - 1;
+                self.buckets[bucket_i] = Bucket::Occupied(offset, value, insertion_i);
+                if self.first.is_none() {
+                    self.first = Some((insertion_i, bucket_i));
+                }
+                self.last = Some((insertion_i, bucket_i));
+                self.size += 1;
+                return;
+            }
+        }
+    }
+}
+
+impl HashTable<SKeyType, SValueType> for DiskStrHashTable {
+    fn new(min_capacity: usize) -> Self {
+        let values = ValueTable::from_storage(Storage::Mem(Vec::new()));
+        Self::from_values(values, min_capacity)
+    }
+
+    fn insert(&mut self, key: SKeyType, value: SValueType) -> Result<(), &'static str> {
+        // Grow the in-memory index at 3/4 load so it never overflows mid-run, even
+        // though the value region on disk can keep growing independently.
+        if (self.size + 1) * 4 > self.capacity * 3 {
+            self.grow();
+        }
+
+        let h = fxhash(&key);
+        let max_attempts = (0.75 * (self.capacity as f64)) as usize;
+        // First free bucket on the probe; a tombstone is only reused once the rest
+        // of the chain has shown the key is not already present.
+        let mut free = None;
+        for i in 0..max_attempts {
+            let bucket_i = (h + i) & (self.capacity - 1);
+            match self.buckets[bucket_i] {
+                Bucket::Occupied(offset, _, insertion_i) if key == self.values.key_at(offset) => {
+                    self.values.set_value_at(offset, value);
+                    self.buckets[bucket_i] = Bucket::Occupied(offset, value, insertion_i);
+                    return Ok(());
+                }
+                Bucket::Deleted => {
+                    free.get_or_insert(bucket_i);
+                }
+                Bucket::Empty => {
+                    free.get_or_insert(bucket_i);
+                    break;
+                }
+                Bucket::Occupied(..) => continue,
+            }
+        }
+        if let Some(bucket_i) = free {
+            // Persist the record first, then record its offset in the bucket.
+            let offset = self.values.store(&key, value);
+            self.by_insertion.push(Some(bucket_i));
+            let insertion_i = self.by_insertion.len() - 1;
+            self.buckets[bucket_i] = Bucket::Occupied(offset, value, insertion_i);
+            if self.first.is_none() {
+                self.first = Some((insertion_i, bucket_i));
+                self.first_key = Some(key.clone());
+            }
+            self.last = Some((insertion_i, bucket_i));
+            self.last_key = Some(key);
+            self.size += 1;
+            return Ok(());
+        }
+        // The probe ran out of attempts without finding a home (a long cluster of
+        // live entries): grow and retry. Insertion stays infallible.
+        self.grow();
+        self.insert(key, value)
+    }
+
+    fn get(&self, key: impl Borrow<str>) -> Option<&SValueType> {
+        let key = key.borrow();
+        let h = fxhash(key);
+        let max_attempts = (0.75 * (self.capacity as f64)) as usize;
+        for i in 0..max_attempts {
+            let bucket_i = (h + i) & (self.capacity - 1);
+            match &self.buckets[bucket_i] {
+                Bucket::Occupied(offset, value, _) if self.values.key_at(*offset) == key => {
+                    return Some(value);
+                }
+                Bucket::Empty => return None,
+                _ => continue,
+            }
+        }
+        None
+    }
+
+    fn remove(&mut self, key: impl Borrow<str>) -> Option<SValueType> {
+        let key = key.borrow();
+        let h = fxhash(key);
+        let max_attempts = (0.75 * (self.capacity as f64)) as usize;
+        for i in 0..max_attempts {
+            let bucket_i = (h + i) & (self.capacity - 1);
+            match self.buckets[bucket_i] {
+                Bucket::Occupied(offset, value, insertion_i)
+                    if self.values.key_at(offset) == key =>
+                {
+                    self.size -= 1;
+                    self.values.free(offset); // Link the record into the free-list
+                    self.buckets[bucket_i] = Bucket::Deleted;
+                    self.by_insertion[insertion_i] = None;
+
+                    while self.by_insertion.pop_if(|e| e.is_none()).is_some() {}
+                    self.last = self.by_insertion.last().map(|index| {
+                        let index = index.expect("No trailing Nones");
+                        (self.by_insertion.len() - 1, index)
+                    });
+
+                    let cur_first = self.first.expect("Had at least len 1").0;
+                    self.first = (cur_first..self.by_insertion.len())
+                        .find_map(|i| self.by_insertion[i].map(|bucket| (i, bucket)));
+
+                    self.refresh_endpoint_keys();
+                    return Some(value);
+                }
+                Bucket::Empty => return None,
+                _ => continue,
+            };
+        }
+        None
+    }
+
+    fn get_last(&self) -> Option<(&SKeyType, &SValueType)> {
+        let (_, bucket_i) = self.last?;
+        match &self.buckets[bucket_i] {
+            Bucket::Occupied(_, value, _) => {
+                Some((self.last_key.as_ref().expect("last key cached"), value))
+            }
+            _ => panic!("Index to deleted entry"),
+        }
+    }
+
+    fn get_first(&self) -> Option<(&SKeyType, &SValueType)> {
+        let (_, bucket_i) = self.first?;
+        match &self.buckets[bucket_i] {
+            Bucket::Occupied(_, value, _) => {
+                Some((self.first_key.as_ref().expect("first key cached"), value))
+            }
+            _ => panic!("Index to deleted entry"),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.size
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mem_insert_get_remove() {
+        let mut table = DiskStrHashTable::new(1000);
+        table.insert("Hello".into(), 1).unwrap();
+        table.insert("World".into(), 2).unwrap();
+        assert_eq!(table.get("Hello"), Some(&1));
+        assert_eq!(table.get("World"), Some(&2));
+
+        table.insert("Hello".into(), 5).unwrap();
+        assert_eq!(table.get("Hello"), Some(&5));
+
+        assert_eq!(table.remove("World"), Some(2));
+        assert_eq!(table.get("World"), None);
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_free_list_reuses_slots() {
+        let mut table = DiskStrHashTable::new(1000);
+        table.insert("aaaa".into(), 1).unwrap();
+        let filled_before = table.values.read_u64(FILLED);
+        table.remove("aaaa");
+        // Same-sized key should reuse the freed record rather than bump FILLED.
+        table.insert("bbbb".into(), 2).unwrap();
+        assert_eq!(table.values.read_u64(FILLED), filled_before);
+        assert_eq!(table.get("bbbb"), Some(&2));
+    }
+}
@@ -8,10 +8,14 @@ use unix_print::unix_println as println;
 
 /// A generic Hash table which keeps insertion history
 pub trait HashTable<K: Hash + Eq, V> {
-    /// Initializes an empty hash map with a given (fixed) capacity
+    /// Initializes an empty hash map sized to hold at least `min_capacity`
+    /// entries. The table grows and rehashes automatically as it fills, so this
+    /// is only a starting hint, not a hard limit.
     fn new(min_capacity: usize) -> Self;
 
-    /// Inserts or replaces an item in the map. Will raise an error if the map is full
+    /// Inserts or replaces an item in the map. The `Result` is retained for API
+    /// compatibility; insertion is infallible under normal operation (the table
+    /// grows to make room) and only an allocation failure could surface an error.
     fn insert(&mut self, key: K, value: V) -> Result<(), &'static str>;
 
     /// Lookup item in the map
@@ -35,16 +39,40 @@ pub trait HashTable<K: Hash + Eq, V> {
 
 #[derive(Debug)]
 #[repr(u8)]
-enum Entry<K, V> {
+enum Bucket<K, V> {
     Empty,
-    /// Occupied contains Key, Value and the index to the ordered vector
-    Occupied(K, V, usize),
-    Deleted,
+    /// Occupied contains Key, Value, the index into the ordered vector and the
+    /// Robin Hood probe distance (how far the entry sits from its ideal bucket).
+    Occupied(K, V, usize, usize),
 }
 
 type SKeyType = CompactString;
 type SValueType = u32;
 
+// Per-slot SwissTable control byte mirroring `buckets`: `EMPTY` for a free slot
+// and otherwise the 7-bit `h2` tag of the resident key (top bit always clear), so
+// the scan can reject most slots without touching a `CompactString`. There is no
+// tombstone tag — Robin Hood backward-shift deletion keeps every chain contiguous.
+//
+// Note on probing strategy: the control bytes started life backing a hashbrown-
+// style group-of-16 SWAR/SIMD tag scan. That was superseded by the Robin Hood
+// open-addressing scheme (with the probe-distance early-out and backward-shift
+// deletion) now used throughout `insert`/`get`/`remove`: the two strategies are
+// mutually exclusive, and Robin Hood's bounded probe-length variance and
+// tombstone-free deletion won out. The control bytes are retained as a cheap
+// one-byte-per-slot tag filter that still spares most key comparisons; the group
+// scan itself is intentionally gone.
+const EMPTY: u8 = 0xFF;
+
+// Smallest (power-of-two) table we ever allocate.
+const MIN_CAPACITY: usize = 8;
+
+/// Low 7 bits of the hash, stored as the one-byte control tag.
+#[inline]
+fn h2(hash: usize) -> u8 {
+    (hash & 0x7F) as u8
+}
+
 #[derive(Debug)]
 pub struct StrHashTable {
     // The map entries
@@ -52,7 +80,10 @@ pub struct StrHashTable {
     // the index of the element as per insertion order (next field)
     // Keeping an index is a simple way in rust to keep a reference without using cells and
     // other Rust structures which would incur a runtime penalty.
-    buckets: Vec<Entry<SKeyType, SValueType>>,
+    buckets: Vec<Bucket<SKeyType, SValueType>>,
+    // Parallel array of control bytes mirroring `buckets`: one tag per slot so
+    // probing can reject non-matching keys before touching the key itself.
+    control: Vec<u8>,
     // The indices per insertion order. Deleted items are set to None, so indices are
     // kept valid and we don't incur the traditional runtime penalty of really removing the items
     by_insertion: Vec<Option<usize>>,
@@ -66,13 +97,48 @@ pub struct StrHashTable {
     last: Option<(usize, usize)>,
 }
 
+impl StrHashTable {
+    /// Doubles the bucket array and rehashes every live entry into it.
+    ///
+    /// Insertion order is preserved: we walk the old `by_insertion` vector in
+    /// order and re-`insert` each surviving entry, so `insertion_i`, `first`,
+    /// `last` and the compacted `by_insertion` are all rebuilt from scratch.
+    fn grow(&mut self) {
+        let new_capacity = self.capacity * 2;
+
+        let mut old_buckets = {
+            let mut buckets = Vec::with_capacity(new_capacity);
+            buckets.resize_with(new_capacity, || Bucket::Empty);
+            core::mem::replace(&mut self.buckets, buckets)
+        };
+        let old_order = core::mem::take(&mut self.by_insertion);
+
+        self.control = alloc::vec![EMPTY; new_capacity];
+        self.capacity = new_capacity;
+        self.size = 0;
+        self.first = None;
+        self.last = None;
+
+        for slot in old_order.into_iter().flatten() {
+            if let Bucket::Occupied(key, value, ..) =
+                core::mem::replace(&mut old_buckets[slot], Bucket::Empty)
+            {
+                // The table was just grown, so this can never recurse or fail.
+                self.insert(key, value)
+                    .expect("rehash into a freshly grown table cannot fail");
+            }
+        }
+    }
+}
+
 impl HashTable<SKeyType, SValueType> for StrHashTable {
     fn new(min_capacity: usize) -> Self {
-        let capacity = min_capacity.next_power_of_two();
+        let capacity = min_capacity.next_power_of_two().max(MIN_CAPACITY);
         let mut buckets = Vec::with_capacity(capacity);
-        buckets.resize_with(capacity, || Entry::Empty);
+        buckets.resize_with(capacity, || Bucket::Empty);
         Self {
             buckets,
+            control: alloc::vec![EMPTY; capacity],
             capacity,
             size: 0,
             by_insertion: Vec::new(),
@@ -82,95 +148,152 @@ impl HashTable<SKeyType, SValueType> for StrHashTable {
     }
 
     fn insert(&mut self, key: SKeyType, value: SValueType) -> Result<(), &'static str> {
-        let h = fxhash(&key);
-        let max_attempts = (0.75 * (self.capacity as f64)) as usize;
-        for i in 0..max_attempts {
-            let bucket_i = (h + i) & (self.capacity - 1);
-            if let Entry::Occupied(k, v, _) = &mut self.buckets[bucket_i]
-                && key == k
-            {
-                *v = value;
-            } else if let Entry::Empty | Entry::Deleted = self.buckets[bucket_i] {
-                // Cross reference structures. Bucket contains K,V and insertion index. Insertion tracks bucket index
-                #[cfg(test)]
-                println!("Adding to bucket {}", bucket_i);
-                self.by_insertion.push(Some(bucket_i));
-                let insertion_i = self.by_insertion.len() - 1;
-                self.buckets[bucket_i] = Entry::Occupied(key, value, insertion_i);
-                if self.first.is_none() {
-                    self.first = Some((insertion_i, bucket_i));
+        let hash = fxhash(&key);
+        let tag = h2(hash);
+        let mask = self.capacity - 1;
+
+        // Update in place if the key is already present. We can stop as soon as we
+        // reach a richer resident: Robin Hood guarantees our key would have evicted
+        // it on the way in, so it cannot be further down the chain.
+        let mut slot = hash & mask;
+        let mut dist = 0;
+        loop {
+            match &mut self.buckets[slot] {
+                Bucket::Occupied(k, v, _, d) => {
+                    if *d < dist {
+                        break;
+                    }
+                    if self.control[slot] == tag && *k == key {
+                        *v = value;
+                        return Ok(());
+                    }
                 }
-                self.last = Some((insertion_i, bucket_i));
-                self.size += 1;
-                return Ok(());
+                Bucket::Empty => break,
             }
+            dist += 1;
+            slot = (slot + 1) & mask;
         }
-        Err("Could not insert. No sufficient slots")
+
+        // Grow at 7/8 load so chains stay short. No tombstones to account for:
+        // backward-shift deletion keeps the table dense.
+        if (self.size + 1) * 8 > self.capacity * 7 {
+            self.grow();
+        }
+        let mask = self.capacity - 1;
+        self.place(hash, key, value, hash & mask, 0);
+        Ok(())
     }
 
-    // Lookup using linear probing
+    // Lookup using Robin Hood linear probing with the early-out invariant.
     fn get(&self, key: impl Borrow<str>) -> Option<&SValueType> {
         let key = key.borrow();
-        let h = fxhash(key);
-        let max_attempts = (0.75 * (self.capacity as f64)) as usize;
-        for i in 0..max_attempts {
-            let bucket_i = (h + i) & (self.capacity - 1);
-            match &self.buckets[bucket_i] {
-                Entry::Occupied(k, value, _insertion_i) if k == key => {
-                    #[cfg(test)]
-                    println!("Found! Slot: {} order: {}", bucket_i, _insertion_i);
-                    return Some(value);
+        let hash = fxhash(key);
+        let tag = h2(hash);
+        let mask = self.capacity - 1;
+
+        let mut slot = hash & mask;
+        let mut dist = 0;
+        loop {
+            match &self.buckets[slot] {
+                Bucket::Occupied(k, value, _insertion_i, d) => {
+                    if *d < dist {
+                        return None;
+                    }
+                    if self.control[slot] == tag && k == key {
+                        #[cfg(test)]
+                        println!("Found! Slot: {} order: {}", slot, _insertion_i);
+                        return Some(value);
+                    }
                 }
-                Entry::Empty => return None,
-                _ => continue,
+                Bucket::Empty => return None,
             }
+            dist += 1;
+            slot = (slot + 1) & mask;
         }
-        None
     }
 
-    // Remove using linear probing and tombstoning (mark as Deleted)
+    // Remove using Robin Hood backward-shift deletion: no tombstone is left
+    // behind; instead the following cluster is shifted back by one to close the gap.
     fn remove(&mut self, key: impl Borrow<str>) -> Option<SValueType> {
         let key = key.borrow();
-        let h = fxhash(key);
-        let max_attempts = (0.75 * (self.capacity as f64)) as usize;
-        for i in 0..max_attempts {
-            let bucket_i = (h + i) & (self.capacity - 1);
-            match self.buckets[bucket_i] {
-                Entry::Occupied(ref k, value, insertion_i) if k == key => {
-                    self.size -= 1;
-
-                    self.buckets[bucket_i] = Entry::Deleted; // Set slot as deleted (tomb-stoning)
-
-                    self.by_insertion[insertion_i] = None; // Respective insertion index also pointing nowhere
-
-                    // Now update first/last
-                    // If we delete an item which is neither first or last this should be no-op
-                    // Let's update last. We can pop items to reuse memory
-                    while self.by_insertion.pop_if(|e| e.is_none()).is_some() {}
-                    self.last = self.by_insertion.last().map(|index| {
-                        let index = index.expect("No trailing Nones");
-                        (self.by_insertion.len() - 1, index)
-                    });
-
-                    // We might have deleted the first, let's advance (No removing, otherwise insertion_indices invalidate)
-                    let cur_first = self.first.expect("Had at least len 1").0;
-                    self.first = (cur_first..self.by_insertion.len())
-                        .find_map(|i| self.by_insertion[i].map(|bucket| (i, bucket)));
-
-                    return Some(value);
+        let hash = fxhash(key);
+        let tag = h2(hash);
+        let mask = self.capacity - 1;
+
+        let mut slot = hash & mask;
+        let mut dist = 0;
+        let found = loop {
+            match &self.buckets[slot] {
+                Bucket::Occupied(k, _, _, d) => {
+                    if *d < dist {
+                        return None;
+                    }
+                    if self.control[slot] == tag && k == key {
+                        break slot;
+                    }
                 }
-                Entry::Empty => return None,
-                _ => continue,
-            };
+                Bucket::Empty => return None,
+            }
+            dist += 1;
+            slot = (slot + 1) & mask;
+        };
+
+        let Bucket::Occupied(_, value, insertion_i, _) =
+            core::mem::replace(&mut self.buckets[found], Bucket::Empty)
+        else {
+            unreachable!("found slot must be occupied")
+        };
+        self.control[found] = EMPTY;
+        self.size -= 1;
+        self.by_insertion[insertion_i] = None; // Respective insertion index also pointing nowhere
+
+        // Walk the following cluster, shifting each entry that is not already in its
+        // ideal bucket (nonzero distance) back by one slot and fixing up its bucket
+        // mapping, until we hit an empty slot or a zero-distance entry.
+        let mut prev = found;
+        let mut i = (found + 1) & mask;
+        loop {
+            match &self.buckets[i] {
+                Bucket::Occupied(_, _, _, d) if *d > 0 => {
+                    let rtag = self.control[i];
+                    self.control[i] = EMPTY;
+                    let Bucket::Occupied(k, v, ins, d) =
+                        core::mem::replace(&mut self.buckets[i], Bucket::Empty)
+                    else {
+                        unreachable!()
+                    };
+                    self.control[prev] = rtag;
+                    self.by_insertion[ins] = Some(prev);
+                    self.buckets[prev] = Bucket::Occupied(k, v, ins, d - 1);
+                    prev = i;
+                    i = (i + 1) & mask;
+                }
+                _ => break,
+            }
         }
-        None
+
+        // Now update first/last exactly as the tombstoning version did — the bucket
+        // mappings in `by_insertion` already reflect the backward shift above.
+        // We can pop trailing holes to reuse memory.
+        while self.by_insertion.pop_if(|e| e.is_none()).is_some() {}
+        self.last = self.by_insertion.last().map(|index| {
+            let index = index.expect("No trailing Nones");
+            (self.by_insertion.len() - 1, index)
+        });
+
+        // We might have deleted the first, let's advance (No removing, otherwise insertion_indices invalidate)
+        let cur_first = self.first.expect("Had at least len 1").0;
+        self.first = (cur_first..self.by_insertion.len())
+            .find_map(|i| self.by_insertion[i].map(|bucket| (i, bucket)));
+
+        Some(value)
     }
 
     /// returns the most recent key-value pair that was either inserted or updated and is still present,
     fn get_last(&self) -> Option<(&SKeyType, &SValueType)> {
         self.last
             .map(|(_, bucket_i)| match &self.buckets[bucket_i] {
-                Entry::Occupied(key, value, _) => (key, value),
+                Bucket::Occupied(key, value, ..) => (key, value),
                 _ => panic!("Index to deleted entry"),
             })
     }
@@ -179,7 +302,7 @@ impl HashTable<SKeyType, SValueType> for StrHashTable {
     fn get_first(&self) -> Option<(&SKeyType, &SValueType)> {
         self.first
             .map(|(_, bucket_i)| match &self.buckets[bucket_i] {
-                Entry::Occupied(key, value, _) => (key, value),
+                Bucket::Occupied(key, value, ..) => (key, value),
                 _ => panic!("Index to deleted entry"),
             })
     }
@@ -193,6 +316,264 @@ impl HashTable<SKeyType, SValueType> for StrHashTable {
     }
 }
 
+impl StrHashTable {
+    /// Iterates over `(&key, &value)` pairs in insertion order, walking the
+    /// `by_insertion` history and skipping the holes left by removed entries.
+    pub fn iter(&self) -> impl Iterator<Item = (&SKeyType, &SValueType)> {
+        self.by_insertion.iter().filter_map(|slot| match &self.buckets[(*slot)?] {
+            Bucket::Occupied(k, v, ..) => Some((k, v)),
+            Bucket::Empty => None,
+        })
+    }
+
+    /// Iterates over `(&key, &value)` pairs in bucket order, which is cheaper than
+    /// [`iter`](Self::iter) when insertion order does not matter.
+    pub fn iter_unordered(&self) -> impl Iterator<Item = (&SKeyType, &SValueType)> {
+        self.buckets.iter().filter_map(|bucket| match bucket {
+            Bucket::Occupied(k, v, ..) => Some((k, v)),
+            Bucket::Empty => None,
+        })
+    }
+
+    /// Empties the table, returning an iterator over its `(key, value)` pairs in
+    /// insertion order.
+    pub fn drain(&mut self) -> impl Iterator<Item = (SKeyType, SValueType)> {
+        let order = core::mem::take(&mut self.by_insertion);
+        let mut drained = Vec::with_capacity(self.size);
+        for slot in order.into_iter().flatten() {
+            if let Bucket::Occupied(k, v, ..) =
+                core::mem::replace(&mut self.buckets[slot], Bucket::Empty)
+            {
+                self.control[slot] = EMPTY;
+                drained.push((k, v));
+            }
+        }
+        self.size = 0;
+        self.first = None;
+        self.last = None;
+        drained.into_iter()
+    }
+
+    /// Returns a view into a single entry, which may be vacant or occupied. Probes
+    /// the table once, so the common `*map.entry(k).or_insert(0) += 1` idiom costs a
+    /// single hash and probe instead of a separate `get` then `insert`.
+    pub fn entry(&mut self, key: SKeyType) -> Entry<'_> {
+        let hash = fxhash(&key);
+        match self.probe(hash, &key) {
+            Probe::Found(bucket) => Entry::Occupied(OccupiedEntry { table: self, bucket }),
+            Probe::Vacant { slot, dist } => {
+                Entry::Vacant(VacantEntry { table: self, key, hash, slot, dist })
+            }
+        }
+    }
+
+    /// Robin Hood placement of a brand-new entry, carrying the element forward
+    /// from `start_slot`/`start_dist` and swapping it with any resident closer to
+    /// its ideal bucket. The caller must already have ensured the key is absent
+    /// and that a free slot exists; `start_slot`/`start_dist` are the point a
+    /// probe for `hash` stopped, so the carry never re-walks the prefix. Returns
+    /// the bucket the new entry ultimately occupies.
+    fn place(
+        &mut self,
+        hash: usize,
+        key: SKeyType,
+        value: SValueType,
+        start_slot: usize,
+        start_dist: usize,
+    ) -> usize {
+        let tag = h2(hash);
+        let mask = self.capacity - 1;
+
+        // Reserve the insertion-order slot for the brand-new entry up front; its
+        // bucket mapping is filled in once it finds a home below.
+        self.by_insertion.push(Some(0));
+        let new_i = self.by_insertion.len() - 1;
+        let mut new_bucket = 0;
+
+        // Robin Hood placement: carry the current element forward, swapping it with
+        // any resident that is closer to its ideal bucket than we are to ours.
+        let (mut ck, mut cv, mut ctag, mut cins, mut cdist) = (key, value, tag, new_i, start_dist);
+        let mut slot = start_slot;
+        loop {
+            match &mut self.buckets[slot] {
+                Bucket::Empty => {
+                    #[cfg(test)]
+                    println!("Adding to bucket {}", slot);
+                    self.control[slot] = ctag;
+                    self.by_insertion[cins] = Some(slot);
+                    if cins == new_i {
+                        new_bucket = slot;
+                    }
+                    self.buckets[slot] = Bucket::Occupied(ck, cv, cins, cdist);
+                    break;
+                }
+                Bucket::Occupied(_, _, _, d) if *d < cdist => {
+                    // The resident is richer than the element we carry: evict it.
+                    let rtag = self.control[slot];
+                    self.control[slot] = ctag;
+                    let evicted =
+                        core::mem::replace(&mut self.buckets[slot], Bucket::Occupied(ck, cv, cins, cdist));
+                    self.by_insertion[cins] = Some(slot);
+                    if cins == new_i {
+                        new_bucket = slot;
+                    }
+                    let Bucket::Occupied(rk, rv, rins, rdist) = evicted else {
+                        unreachable!("control byte and bucket disagree")
+                    };
+                    (ck, cv, ctag, cins, cdist) = (rk, rv, rtag, rins, rdist);
+                    cdist += 1;
+                    slot = (slot + 1) & mask;
+                }
+                Bucket::Occupied(..) => {
+                    cdist += 1;
+                    slot = (slot + 1) & mask;
+                }
+            }
+        }
+        self.size += 1;
+
+        // The newest entry is always the new last; the first keeps its insertion
+        // index but its bucket may have moved during the carry above.
+        self.last = Some((new_i, new_bucket));
+        self.first = Some(match self.first {
+            Some((fi, _)) => (fi, self.by_insertion[fi].expect("first entry still present")),
+            None => (new_i, new_bucket),
+        });
+        new_bucket
+    }
+
+    /// Robin Hood probe for `key`. On a hit returns [`Probe::Found`] with its
+    /// bucket; on a miss returns [`Probe::Vacant`] with the slot (and its probe
+    /// distance) where the search stopped — exactly the point a subsequent
+    /// [`place`](Self::place) should resume carrying from, so the vacant `entry`
+    /// path costs a single hash and a single probe.
+    fn probe(&self, hash: usize, key: &str) -> Probe {
+        let tag = h2(hash);
+        let mask = self.capacity - 1;
+        let mut slot = hash & mask;
+        let mut dist = 0;
+        loop {
+            match &self.buckets[slot] {
+                Bucket::Occupied(k, _, _, d) => {
+                    if *d < dist {
+                        return Probe::Vacant { slot, dist };
+                    }
+                    if self.control[slot] == tag && k == key {
+                        return Probe::Found(slot);
+                    }
+                }
+                Bucket::Empty => return Probe::Vacant { slot, dist },
+            }
+            dist += 1;
+            slot = (slot + 1) & mask;
+        }
+    }
+}
+
+/// Outcome of a [`StrHashTable::probe`]: either the bucket holding the key, or
+/// the slot/distance where placement of an absent key should begin.
+enum Probe {
+    Found(usize),
+    Vacant { slot: usize, dist: usize },
+}
+
+/// A view into a single map entry, obtained from [`StrHashTable::entry`].
+pub enum Entry<'a> {
+    Occupied(OccupiedEntry<'a>),
+    Vacant(VacantEntry<'a>),
+}
+
+/// A view into an occupied entry.
+pub struct OccupiedEntry<'a> {
+    table: &'a mut StrHashTable,
+    bucket: usize,
+}
+
+/// A view into a vacant entry.
+pub struct VacantEntry<'a> {
+    table: &'a mut StrHashTable,
+    key: SKeyType,
+    // The hash and placement point computed by `entry`, threaded through so
+    // insertion neither re-hashes the key nor re-probes the table.
+    hash: usize,
+    slot: usize,
+    dist: usize,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Converts the entry into a mutable reference to its value.
+    pub fn into_mut(self) -> &'a mut SValueType {
+        match &mut self.table.buckets[self.bucket] {
+            Bucket::Occupied(_, v, ..) => v,
+            Bucket::Empty => unreachable!("occupied entry points at an empty bucket"),
+        }
+    }
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Inserts `value` and returns a mutable reference to it.
+    pub fn insert(self, value: SValueType) -> &'a mut SValueType {
+        let VacantEntry { table, key, hash, slot, dist } = self;
+        // Reuse the probe from `entry` unless a resize relocates entries, which is
+        // the only case that costs a second probe.
+        let (slot, dist) = if (table.size + 1) * 8 > table.capacity * 7 {
+            table.grow();
+            match table.probe(hash, &key) {
+                Probe::Vacant { slot, dist } => (slot, dist),
+                Probe::Found(_) => unreachable!("vacant entry cannot already be present"),
+            }
+        } else {
+            (slot, dist)
+        };
+        let bucket = table.place(hash, key, value, slot, dist);
+        match &mut table.buckets[bucket] {
+            Bucket::Occupied(_, v, ..) => v,
+            Bucket::Empty => unreachable!("just-inserted key is present"),
+        }
+    }
+}
+
+impl<'a> Entry<'a> {
+    /// Ensures a value is in the entry by inserting `default` if vacant, then
+    /// returns a mutable reference to the value.
+    pub fn or_insert(self, default: SValueType) -> &'a mut SValueType {
+        self.or_insert_with(|| default)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default` if
+    /// vacant, then returns a mutable reference to the value.
+    pub fn or_insert_with(self, default: impl FnOnce() -> SValueType) -> &'a mut SValueType {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Applies `f` to the value in place if the entry is occupied.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut SValueType)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            if let Bucket::Occupied(_, v, ..) = &mut entry.table.buckets[entry.bucket] {
+                f(v);
+            }
+        }
+        self
+    }
+}
+
+impl FromIterator<(SKeyType, SValueType)> for StrHashTable {
+    fn from_iter<T: IntoIterator<Item = (SKeyType, SValueType)>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        let mut table = StrHashTable::new(lower.max(MIN_CAPACITY));
+        for (key, value) in iter {
+            table
+                .insert(key, value)
+                .expect("growth keeps the table infallible");
+        }
+        table
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,4 +643,68 @@ mod tests {
         assert_eq!(table.get_first(), None);
         assert_eq!(table.get_last(), None);
     }
+
+    #[test]
+    fn test_entry_or_insert_and_modify() {
+        let mut table = StrHashTable::new(16);
+        for word in ["a", "b", "a", "a", "b", "c"] {
+            *table.entry(word.into()).or_insert(0) += 1;
+        }
+        assert_eq!(table.get("a"), Some(&3));
+        assert_eq!(table.get("b"), Some(&2));
+        assert_eq!(table.get("c"), Some(&1));
+
+        table.entry("a".into()).and_modify(|v| *v = 100);
+        assert_eq!(table.get("a"), Some(&100));
+        // and_modify leaves a vacant entry untouched
+        table.entry("z".into()).and_modify(|v| *v = 7);
+        assert_eq!(table.get("z"), None);
+    }
+
+    #[test]
+    fn test_iter_insertion_order_and_from_iter() {
+        let table = StrHashTable::from_iter([
+            (CompactString::from("one"), 1u32),
+            (CompactString::from("two"), 2),
+            (CompactString::from("three"), 3),
+        ]);
+        let collected: Vec<_> = table.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        assert_eq!(
+            collected,
+            alloc::vec![
+                (CompactString::from("one"), 1),
+                (CompactString::from("two"), 2),
+                (CompactString::from("three"), 3),
+            ]
+        );
+        assert_eq!(table.iter_unordered().count(), 3);
+    }
+
+    #[test]
+    fn test_drain_empties_in_order() {
+        let mut table = StrHashTable::new(16);
+        table.insert("x".into(), 1).unwrap();
+        table.insert("y".into(), 2).unwrap();
+        let drained: Vec<_> = table.drain().collect();
+        assert_eq!(drained, alloc::vec![("x".into(), 1), ("y".into(), 2)]);
+        assert!(table.is_empty());
+        assert_eq!(table.get("x"), None);
+    }
+
+    #[test]
+    fn test_backward_shift_keeps_chain() {
+        // Remove a key from a tiny, partly filled table (removal closes the gap by
+        // backward shift rather than leaving a tombstone), then make sure every
+        // survivor is still reachable.
+        let mut table = StrHashTable::new(MIN_CAPACITY);
+        for i in 0..5 {
+            table.insert(CompactString::from(alloc::format!("k{i}")), i).unwrap();
+        }
+        table.remove("k2");
+        assert_eq!(table.get("k2"), None);
+        for i in [0, 1, 3, 4] {
+            assert_eq!(table.get(alloc::format!("k{i}")), Some(&i));
+        }
+        assert_eq!(table.len(), 4);
+    }
 }
@@ -0,0 +1,5 @@
+extern crate alloc;
+
+pub mod disk_hash_table;
+pub mod fixed_hash_table;
+pub mod sync_hash_table;
@@ -6,19 +6,22 @@ use performance_rust::fixed_hash_table::{HashTable, StrHashTable};
 fn main() {
     let reader = BufReader::new(File::open("98-0.txt").expect("Cannot open file 98-0.txt"));
 
-    let mut map = StrHashTable::new(2_000_000);
+    // The table now grows and rehashes on demand, so a small starting hint is enough.
+    let mut map = StrHashTable::new(1024);
 
     for line in reader.lines() {
         for word in line.unwrap().split_whitespace() {
-            match map.get(word) {
-                None => map.insert(word.into(), 1).unwrap(),
-                Some(count) => map.insert(word.into(), count + 1).unwrap(),
-            }
+            *map.entry(word.into()).or_insert(0) += 1;
         }
     }
 
     println!("Text contains {} unique words", map.len());
 
+    println!("\nFirst 10 words by first appearance:");
+    for (word, count) in map.iter().take(10) {
+        println!("{}: {}", word, count);
+    }
+
     println!("\nExample of few frequencies:");
     for word in ["The", "lazy", "fox", "jumps", "over", "the", "fence"] {
         match map.get(word) {
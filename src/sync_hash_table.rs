@@ -0,0 +1,376 @@
+//! A concurrent sibling of [`StrHashTable`](crate::fixed_hash_table::StrHashTable)
+//! with wait-free reads and mutex-serialized writes, modelled on the `horde`
+//! `SyncTable` approach.
+//!
+//! Readers never take a lock: they pin the current epoch, load the bucket array
+//! behind an `Acquire` and scan the [`AtomicU8`] control bytes, only touching a
+//! slot once its tag matches. Writers serialize through a single
+//! [`parking_lot::Mutex`] and publish a slot by writing its control byte **last**
+//! (with `Release`), so a reader that observes the tag is guaranteed to see the
+//! fully-written key and value. Resizes retire the old bucket array through
+//! epoch-based reclamation, so it is only freed once every in-flight reader has
+//! left its critical section.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU8, AtomicU32, AtomicUsize, Ordering};
+
+use compact_str::CompactString;
+use crossbeam_epoch::{self as epoch, Atomic, Owned, Shared};
+use fxhash::hash as fxhash;
+use parking_lot::Mutex;
+
+type SKeyType = CompactString;
+type SValueType = u32;
+
+// Control-byte tags: `EMPTY` for a never-used slot, `DELETED` for a tombstone and
+// otherwise the 7-bit `h2` of the key. Unlike the single-threaded table, which has
+// no tombstone tag, this table marks removed slots with `DELETED`.
+const EMPTY: u8 = 0xFF;
+const DELETED: u8 = 0x80;
+
+const MIN_CAPACITY: usize = 8;
+
+/// Low 7 bits of the hash, stored as the one-byte control tag.
+#[inline]
+fn h2(hash: usize) -> u8 {
+    (hash & 0x7F) as u8
+}
+
+/// A single bucket payload. `UnsafeCell` because readers dereference it without a
+/// lock; its initial publication is ordered by the matching control byte. The
+/// value is an [`AtomicU32`] so that in-place updates by a writer synchronize
+/// with concurrent readers on their own Release/Acquire edge, rather than relying
+/// on the control byte (which only orders the initial publication).
+struct Slot(UnsafeCell<MaybeUninit<(SKeyType, AtomicU32)>>);
+
+// SAFETY: access to the payload is synchronized through the parallel control byte
+// (writers release, readers acquire), and the type inside is itself `Send + Sync`.
+unsafe impl Sync for Slot {}
+unsafe impl Send for Slot {}
+
+impl Slot {
+    fn empty() -> Self {
+        Self(UnsafeCell::new(MaybeUninit::uninit()))
+    }
+
+    /// Raw pointer to the payload cell. Callers must uphold the control-byte
+    /// synchronization discipline described on [`Slot`].
+    fn get(&self) -> *mut MaybeUninit<(SKeyType, AtomicU32)> {
+        self.0.get()
+    }
+}
+
+/// One generation of the bucket array. Replaced wholesale on resize and retired
+/// through the epoch reclaimer.
+struct Table {
+    control: Vec<AtomicU8>,
+    slots: Vec<Slot>,
+    capacity: usize,
+}
+
+impl Table {
+    fn with_capacity(capacity: usize) -> Self {
+        let mut control = Vec::with_capacity(capacity);
+        let mut slots = Vec::with_capacity(capacity);
+        for _ in 0..capacity {
+            control.push(AtomicU8::new(EMPTY));
+            slots.push(Slot::empty());
+        }
+        Self {
+            control,
+            slots,
+            capacity,
+        }
+    }
+
+    /// Raw, unsynchronized placement used while rebuilding a table during resize
+    /// (the caller holds the write lock and owns the table exclusively). Assumes a
+    /// free slot exists — guaranteed by the load factor.
+    fn raw_put(&self, key: SKeyType, value: SValueType) {
+        let hash = fxhash(&key);
+        let tag = h2(hash);
+        let mask = self.capacity - 1;
+        let mut slot = hash & mask;
+        loop {
+            if self.control[slot].load(Ordering::Relaxed) == EMPTY {
+                // SAFETY: exclusive access during resize; slot is currently empty.
+                unsafe { (*self.slots[slot].get()).write((key, AtomicU32::new(value))) };
+                self.control[slot].store(tag, Ordering::Relaxed);
+                return;
+            }
+            slot = (slot + 1) & mask;
+        }
+    }
+}
+
+impl Drop for Table {
+    fn drop(&mut self) {
+        // Every slot whose control byte was ever written (occupied or tombstoned)
+        // holds an initialized payload that must be dropped.
+        for (i, control) in self.control.iter().enumerate() {
+            if control.load(Ordering::Relaxed) != EMPTY {
+                // SAFETY: retired after all readers left; we own it exclusively now.
+                unsafe { (*self.slots[i].get()).assume_init_drop() };
+            }
+        }
+    }
+}
+
+/// A thread-safe, insertion-agnostic string map supporting wait-free reads.
+pub struct SyncStrHashTable {
+    table: Atomic<Table>,
+    // Writers serialize here; the unit payload is just the exclusion token.
+    write: Mutex<()>,
+    size: AtomicUsize,
+    tombstones: AtomicUsize,
+}
+
+// SAFETY: all mutation is funneled through `write` and the epoch-protected
+// `table`; the interior `UnsafeCell`s are only published via atomic control bytes.
+unsafe impl Sync for SyncStrHashTable {}
+unsafe impl Send for SyncStrHashTable {}
+
+impl SyncStrHashTable {
+    /// Creates an empty table sized to at least `min_capacity` entries.
+    pub fn new(min_capacity: usize) -> Self {
+        let capacity = min_capacity.next_power_of_two().max(MIN_CAPACITY);
+        Self {
+            table: Atomic::new(Table::with_capacity(capacity)),
+            write: Mutex::new(()),
+            size: AtomicUsize::new(0),
+            tombstones: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait-free lookup returning a clone of the value if present.
+    pub fn get(&self, key: impl Borrow<str>) -> Option<SValueType> {
+        let key = key.borrow();
+        let hash = fxhash(key);
+        let tag = h2(hash);
+
+        let guard = epoch::pin();
+        // SAFETY: the table pointer is never null and is kept alive by the pin.
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        let mask = table.capacity - 1;
+        let mut slot = hash & mask;
+        for _ in 0..table.capacity {
+            match table.control[slot].load(Ordering::Acquire) {
+                EMPTY => return None,
+                c if c == tag => {
+                    // SAFETY: the tag was published with Release after the payload
+                    // was written, so this Acquire load sees a complete entry.
+                    let (k, v) = unsafe { (*table.slots[slot].get()).assume_init_ref() };
+                    if k == key {
+                        // Acquire pairs with the writer's Release store on update.
+                        return Some(v.load(Ordering::Acquire));
+                    }
+                }
+                _ => {}
+            }
+            slot = (slot + 1) & mask;
+        }
+        None
+    }
+
+    /// Inserts or replaces an entry. Serialized against other writers.
+    pub fn insert(&self, key: SKeyType, value: SValueType) {
+        let _writer = self.write.lock();
+
+        // Grow at 7/8 load (live + tombstones) before we need the slot.
+        if (self.size.load(Ordering::Relaxed) + self.tombstones.load(Ordering::Relaxed) + 1) * 8
+            > self.current_capacity() * 7
+        {
+            self.resize();
+        }
+
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        let hash = fxhash(&key);
+        let tag = h2(hash);
+        let mask = table.capacity - 1;
+
+        // Find the first never-used slot, probing past tombstones and other keys.
+        // A tombstoned slot is deliberately *not* reused in place: a wait-free
+        // reader that matched the dead key's tag before it was removed may still be
+        // dereferencing that payload, so overwriting or dropping it here would be a
+        // use-after-free. Tombstone payloads are reclaimed only when the whole
+        // table is retired on resize, which the epoch reclaimer makes safe. The
+        // load factor (which counts tombstones) guarantees an empty slot exists.
+        let mut slot = hash & mask;
+        loop {
+            match table.control[slot].load(Ordering::Acquire) {
+                EMPTY => break,
+                c if c == tag => {
+                    // SAFETY: published entry; see `get`.
+                    let existing = unsafe { (*table.slots[slot].get()).assume_init_ref() };
+                    if existing.0 == key {
+                        // Release so a concurrent reader's Acquire load observes it.
+                        existing.1.store(value, Ordering::Release);
+                        return;
+                    }
+                }
+                _ => {}
+            }
+            slot = (slot + 1) & mask;
+        }
+
+        // Write the payload into the empty slot, then publish it by storing the tag
+        // last (Release), so a reader that observes the tag sees a complete entry.
+        // SAFETY: exclusive write access under the writer lock; the slot is empty.
+        unsafe { (*table.slots[slot].get()).write((key, AtomicU32::new(value))) };
+        table.control[slot].store(tag, Ordering::Release);
+
+        self.size.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Removes an entry, returning its value if present. Serialized against other
+    /// writers. Leaves a tombstone; the payload is reclaimed when the table is
+    /// next retired, so concurrent readers always see a consistent entry.
+    pub fn remove(&self, key: impl Borrow<str>) -> Option<SValueType> {
+        let _writer = self.write.lock();
+
+        let key = key.borrow();
+        let hash = fxhash(key);
+        let tag = h2(hash);
+
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        let mask = table.capacity - 1;
+
+        let mut slot = hash & mask;
+        for _ in 0..table.capacity {
+            match table.control[slot].load(Ordering::Acquire) {
+                EMPTY => return None,
+                c if c == tag => {
+                    // SAFETY: published entry; see `get`.
+                    let (k, v) = unsafe { (*table.slots[slot].get()).assume_init_ref() };
+                    if k == key {
+                        let value = v.load(Ordering::Acquire);
+                        table.control[slot].store(DELETED, Ordering::Release);
+                        self.size.fetch_sub(1, Ordering::Relaxed);
+                        self.tombstones.fetch_add(1, Ordering::Relaxed);
+                        return Some(value);
+                    }
+                }
+                _ => {}
+            }
+            slot = (slot + 1) & mask;
+        }
+        None
+    }
+
+    /// Number of live entries.
+    pub fn len(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Whether the table holds no live entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn current_capacity(&self) -> usize {
+        let guard = epoch::pin();
+        unsafe { self.table.load(Ordering::Acquire, &guard).deref() }.capacity
+    }
+
+    /// Allocates a larger table, rehashes every live entry into it, publishes it
+    /// and defers destruction of the old generation until readers have drained.
+    /// Caller must hold the writer lock.
+    fn resize(&self) {
+        let guard = epoch::pin();
+        let old_shared: Shared<Table> = self.table.load(Ordering::Acquire, &guard);
+        let old = unsafe { old_shared.deref() };
+
+        let new = Table::with_capacity(old.capacity * 2);
+        for (i, control) in old.control.iter().enumerate() {
+            if control.load(Ordering::Relaxed) < DELETED {
+                // Occupied (tags are 0..=0x7F); clone into the new generation so the
+                // old one stays valid for in-flight readers until it is retired.
+                let (k, v) = unsafe { (*old.slots[i].get()).assume_init_ref() };
+                new.raw_put(k.clone(), v.load(Ordering::Relaxed));
+            }
+        }
+
+        self.tombstones.store(0, Ordering::Relaxed);
+        self.table.store(Owned::new(new), Ordering::Release);
+        // SAFETY: the old table is now unreachable by any thread that pins after
+        // this point; existing readers are protected by the epoch guard.
+        unsafe { guard.defer_destroy(old_shared) };
+    }
+}
+
+impl Drop for SyncStrHashTable {
+    fn drop(&mut self) {
+        // No other threads can reference us; reclaim the live table immediately.
+        let guard = unsafe { epoch::unprotected() };
+        let shared = self.table.swap(Shared::null(), Ordering::Relaxed, guard);
+        if !shared.is_null() {
+            // SAFETY: exclusive ownership at drop time.
+            drop(unsafe { shared.into_owned() });
+        }
+    }
+}
+
+/// A cheaply-cloneable handle so the same table can be sharded across threads,
+/// e.g. `reader.lines()` fanned out over a thread pool.
+pub type SharedStrHashTable = Arc<SyncStrHashTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_basic_insert_get_remove() {
+        let table = SyncStrHashTable::new(16);
+        table.insert("Hello".into(), 1);
+        table.insert("World".into(), 2);
+        assert_eq!(table.get("Hello"), Some(1));
+        assert_eq!(table.get("World"), Some(2));
+        assert_eq!(table.get("missing"), None);
+
+        table.insert("Hello".into(), 10);
+        assert_eq!(table.get("Hello"), Some(10));
+
+        assert_eq!(table.remove("World"), Some(2));
+        assert_eq!(table.get("World"), None);
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_grows_under_load() {
+        let table = SyncStrHashTable::new(MIN_CAPACITY);
+        for i in 0..1000u32 {
+            table.insert(CompactString::from(alloc::format!("k{i}")), i);
+        }
+        assert_eq!(table.len(), 1000);
+        for i in 0..1000u32 {
+            assert_eq!(table.get(alloc::format!("k{i}")), Some(i));
+        }
+    }
+
+    #[test]
+    fn test_sharded_across_threads() {
+        let table: Arc<SyncStrHashTable> = Arc::new(SyncStrHashTable::new(1024));
+        thread::scope(|s| {
+            for t in 0..4u32 {
+                let table = Arc::clone(&table);
+                s.spawn(move || {
+                    for i in 0..250u32 {
+                        let n = t * 250 + i;
+                        table.insert(CompactString::from(alloc::format!("w{n}")), n);
+                    }
+                });
+            }
+        });
+        assert_eq!(table.len(), 1000);
+        assert_eq!(table.get("w0"), Some(0));
+        assert_eq!(table.get("w999"), Some(999));
+    }
+}